@@ -0,0 +1,162 @@
+//! Loads variables from a dotenv-formatted file to use as an additional [`expand_with`] lookup
+//! source, without ever touching the process environment.
+
+use std::{collections::HashMap, path::Path, path::PathBuf};
+
+use bstr::{BString, ByteSlice};
+
+use crate::{errors::ExpandError, expand_with};
+
+/// Parses a dotenv-formatted file at `path` into a map of variable name to value.
+///
+/// Lines that are blank or start with `#` (after trimming leading whitespace) are skipped. A
+/// leading `export ` on a line is ignored, matching the shells dotenv files are usually sourced
+/// from. Values may optionally be wrapped in matching single or double quotes, which are stripped;
+/// unquoted values are used as-is after trimming surrounding whitespace.
+///
+/// # Errors
+///
+/// Returns [`ExpandError::DotenvRead`] if `path` cannot be read, or [`ExpandError::DotenvParse`]
+/// if a non-empty, non-comment line isn't a valid `KEY=VALUE` pair.
+pub fn from_dotenv(path: impl AsRef<Path>) -> Result<HashMap<String, BString>, ExpandError> {
+    let path = path.as_ref();
+    let contents = std::fs::read(path)
+        .map_err(|err| ExpandError::DotenvRead(format!("{}: {err}", path.display())))?;
+
+    let mut vars = HashMap::new();
+
+    for (i, line) in contents.lines().enumerate() {
+        let line_num = i + 1;
+        let line = line.trim();
+
+        if line.is_empty() || line.starts_with(b"#") {
+            continue;
+        }
+
+        let line = line.strip_prefix(b"export ").unwrap_or(line);
+
+        let Some(eq) = line.find_byte(b'=') else {
+            return Err(ExpandError::DotenvParse {
+                line: line_num,
+                reason: "expected KEY=VALUE".to_string(),
+            });
+        };
+
+        let key = line[..eq].trim();
+        let value = line[eq + 1..].trim();
+
+        if key.is_empty() || !key.iter().all(|&b| b.is_ascii_alphanumeric() || b == b'_') {
+            return Err(ExpandError::DotenvParse {
+                line: line_num,
+                reason: format!("invalid variable name '{}'", key.to_str_lossy()),
+            });
+        }
+
+        let value = match (value.first(), value.last()) {
+            (Some(b'"'), Some(b'"')) | (Some(b'\''), Some(b'\'')) if value.len() >= 2 => {
+                &value[1..value.len() - 1]
+            }
+            _ => value,
+        };
+
+        vars.insert(key.to_str_lossy().into_owned(), BString::from(value));
+    }
+
+    Ok(vars)
+}
+
+/// Like [`expand_with`](crate::expand_with), but resolves variables from the dotenv file at
+/// `path` first, falling back to the process environment for names it doesn't define.
+///
+/// # Errors
+///
+/// In addition to the errors documented on [`expand`](crate::expand), returns
+/// [`ExpandError::DotenvRead`] or [`ExpandError::DotenvParse`] if `path` cannot be loaded.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// # use expandenv::dotenv::expand_with_dotenv;
+/// # fn main() -> Result<(), expandenv::errors::ExpandError> {
+/// let path = expand_with_dotenv("$DATABASE_URL", "./.env")?;
+/// # Ok(())
+/// # }
+/// ```
+pub fn expand_with_dotenv<S: AsRef<[u8]>>(
+    s: S,
+    path: impl AsRef<Path>,
+) -> Result<PathBuf, ExpandError> {
+    let vars = from_dotenv(path)?;
+
+    expand_with(s, |name| {
+        vars.get(name)
+            .cloned()
+            .or_else(|| std::env::var(name).ok().map(BString::from))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    /// Writes `contents` to a uniquely-named file in the system temp directory and returns its
+    /// path; the caller is responsible for removing it.
+    fn write_temp_dotenv(name: &str, contents: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("expandenv_test_{name}.env"));
+        std::fs::write(&path, contents).expect("failed to write temp dotenv file");
+        path
+    }
+
+    #[test]
+    fn test_from_dotenv_parses_basic_file() {
+        let path = write_temp_dotenv(
+            "basic",
+            "# a comment\n\nexport FOO=bar\nBAZ=\"quoted value\"\nQUX='also quoted'\n",
+        );
+
+        let vars = from_dotenv(&path).expect("failed to parse dotenv file");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(vars.get("FOO"), Some(&BString::from("bar")));
+        assert_eq!(vars.get("BAZ"), Some(&BString::from("quoted value")));
+        assert_eq!(vars.get("QUX"), Some(&BString::from("also quoted")));
+    }
+
+    #[test]
+    fn test_from_dotenv_rejects_malformed_line() {
+        let path = write_temp_dotenv("malformed", "NOT_A_VALID_LINE\n");
+
+        let result = from_dotenv(&path);
+        std::fs::remove_file(&path).ok();
+
+        match result {
+            Err(ExpandError::DotenvParse { line, .. }) => assert_eq!(line, 1),
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_from_dotenv_missing_file_errors() {
+        match from_dotenv("./this_file_almost_certainly_does_not_exist.env") {
+            Err(ExpandError::DotenvRead(_)) => {}
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_expand_with_dotenv_prefers_file_over_process_env() {
+        const TEST_ENVVAR_KEY: &str = "__EXPANDENV_DOTENV_TEST_VAR";
+        unsafe {
+            std::env::set_var(TEST_ENVVAR_KEY, "from_process");
+        }
+        let path = write_temp_dotenv("precedence", &format!("{TEST_ENVVAR_KEY}=from_file\n"));
+
+        let actual = expand_with_dotenv(format!("${TEST_ENVVAR_KEY}"), &path)
+            .expect("failed to expand from dotenv file");
+        std::fs::remove_file(&path).ok();
+
+        assert_eq!(PathBuf::from("from_file"), actual);
+    }
+}
@@ -1,7 +1,13 @@
 #[derive(Debug, thiserror::Error)]
 pub enum ExpandError {
-    #[error("matched envvar regex but failed to capture envvar")]
-    EmptyEnvvarCapture,
     #[error("failed to get value of envvar: {0}")]
     EnvvarReadError(String),
+    #[error("no such user: {0}")]
+    UnknownUser(String),
+    #[error("{name}: {message}")]
+    RequiredVarUnset { name: String, message: String },
+    #[error("failed to read dotenv file: {0}")]
+    DotenvRead(String),
+    #[error("dotenv file, line {line}: {reason}")]
+    DotenvParse { line: usize, reason: String },
 }
@@ -51,14 +51,15 @@ let path = expand("~/${MISSING_VAR:-$FOO}/file.txt")?;
 ```
 */
 
-use std::{path::PathBuf, sync::LazyLock};
+use std::{borrow::Cow, collections::HashMap, path::PathBuf, sync::LazyLock};
 
 use bstr::{BString, ByteSlice, ByteVec};
 use directories_next::BaseDirs;
-use regex::Regex;
+use regex::bytes::Regex;
 
 use crate::errors::ExpandError;
 
+pub mod dotenv;
 pub mod errors;
 
 /// Lazy wrapper around [`directories_next::BaseDirs::new`].
@@ -66,116 +67,430 @@ static BASE_DIRS: LazyLock<BaseDirs> =
     LazyLock::new(|| BaseDirs::new().expect("failed to locate users home directory"));
 
 /// Mimic's the behavior of [`PathBuf::components`] by extracting environment variables as their
-/// own components in a [`Vec<BString>`].
+/// own components in a [`Vec<&[u8]>`].
+///
+/// Works byte-by-byte rather than char-by-char, so non-UTF8 bytes in `s` pass through untouched
+/// instead of being rejected or replaced; the envvar syntax itself (`$`, `{`, `}`, and variable
+/// names) is always plain ASCII, so scanning a byte at a time never misreads a multi-byte
+/// sequence as one of those.
 ///
 /// # Arguments
 ///
-/// `s` - Input string
-fn __parse_string_components<B: AsRef<[u8]>>(s: B) -> Vec<BString> {
-    let s = s.as_ref();
-
+/// `s` - Input bytes
+fn __parse_string_components(s: &[u8]) -> Vec<&[u8]> {
     // how many braces have opened
     let mut brace_count: u8 = 0;
     let mut components = vec![];
-    let mut current_component = BString::new(vec![]);
+    let mut start = 0;
     let mut parse_as_envvar = false;
 
-    for c in s.chars() {
-        match c {
-            '$' => {
+    for (i, &b) in s.iter().enumerate() {
+        match b {
+            // a '$' nested inside an already-open braced envvar (e.g. the fallback of
+            // `${FOO:-${BAR}}`) is just part of that envvar's text, not a new component
+            b'$' if !parse_as_envvar || brace_count == 0 => {
                 // start of envvar, save current component
-                components.push(current_component.clone());
-                current_component.clear();
-                current_component.push_char(c);
+                components.push(&s[start..i]);
+                start = i;
                 parse_as_envvar = true;
-                continue;
             }
 
-            '{' if parse_as_envvar => brace_count = brace_count.saturating_add(1),
+            b'{' if parse_as_envvar => brace_count = brace_count.saturating_add(1),
 
-            '}' if parse_as_envvar && brace_count == 1 => {
+            b'}' if parse_as_envvar && brace_count == 1 => {
                 // end of braced envvar, save as component
-                current_component.push_char(c);
-                components.push(current_component.clone());
-                current_component.clear();
+                components.push(&s[start..=i]);
+                start = i + 1;
                 parse_as_envvar = false;
                 brace_count -= 1;
-                continue;
             }
-            '}' if parse_as_envvar => brace_count = brace_count.saturating_sub(1),
+            b'}' if parse_as_envvar => brace_count = brace_count.saturating_sub(1),
 
-            c if parse_as_envvar && brace_count == 0 && !c.is_alphanumeric() => {
+            // envvar names may contain letters, numbers, and underscores
+            b if parse_as_envvar
+                && brace_count == 0
+                && !(b.is_ascii_alphanumeric() || b == b'_') =>
+            {
                 // end of envvar without braces, save as component
-                current_component.push_char(c);
-                components.push(current_component.clone());
-                current_component.clear();
+                components.push(&s[start..=i]);
+                start = i + 1;
                 parse_as_envvar = false;
-                continue;
             }
 
             _ => {}
         }
-
-        current_component.push_char(c);
     }
 
-    components.push(current_component);
+    components.push(&s[start..]);
 
     components
 }
 
-/// Convert a `&str` slice into a `PathBuf`, expanding envvars and the leading tilde `~`, if it
-/// is there.
+/// Splits an unbraced envvar component (e.g. `$FOO!`) into the envvar text (`FOO`) and whatever
+/// trailing, non-envvar byte ended up tacked onto it by [`__parse_string_components`] (`!`), if
+/// any. Braced components (e.g. `${FOO}`) never have trailing bytes.
 ///
-/// The tilde (`~`) expands into the users home directory as defined by [`directories_next::BaseDirs::home_dir`].
-///
-/// Environment variables expand into their value, optionally expanding a fallback value if the var
-/// cannot be read. Envvars may contain letters, numbers, and underscores (`_`), but they _must_ start
-/// with either a letter or an underscore after the dollar sign (`$`). Although more complicated
-/// syntax is technically allowed by most programming languages, I will not be supporting anything
-/// other than this basic structure because this is what most shells support and if you're doing
-/// something different, ask yourself why.
-///
-/// # Arguments
-///
-/// - `s`: String to expand and convert
-///
-/// # Errors
+/// Neither a bare `$` (nothing follows it) nor an unterminated `${...}` (no closing `}`) is a
+/// well-formed envvar reference, so both are handed back whole as `body`; the caller falls back
+/// to treating an unrecognized `body` as literal text.
+fn __split_envvar_component(comp: &[u8]) -> (&[u8], &[u8]) {
+    if comp.len() <= 1 {
+        return (comp, b"");
+    }
+
+    if comp.get(1) == Some(&b'{') {
+        return if comp.last() == Some(&b'}') {
+            (&comp[2..comp.len() - 1], b"")
+        } else {
+            (comp, b"")
+        };
+    }
+
+    match comp.last() {
+        Some(b) if !(b.is_ascii_alphanumeric() || *b == b'_') => {
+            (&comp[1..comp.len() - 1], &comp[comp.len() - 1..])
+        }
+        _ => (&comp[1..], b""),
+    }
+}
+
+/// Evaluates one of the POSIX parameter-expansion operators (`:-`, `:=`, `:+`, `:?`) given the
+/// current value of the variable being expanded (`None` if unset, `Some` otherwise, empty or
+/// not). `word` is the operator's still-unexpanded operand.
 ///
-/// An error is returned if:
+/// `assigned` records variables set by a `:=` so that later references to the same name within
+/// this [`expand_with`] call see the assigned value, without requiring `lookup` itself to support
+/// writes.
+fn __eval_operator<F>(
+    name: &str,
+    op: &str,
+    word: &[u8],
+    current: Option<BString>,
+    lookup: &mut F,
+    assigned: &mut HashMap<String, BString>,
+) -> Result<BString, ExpandError>
+where
+    F: FnMut(&str) -> Option<BString>,
+{
+    let unset_or_empty = current.as_ref().is_none_or(|v| v.is_empty());
+
+    match op {
+        ":-" => {
+            if unset_or_empty {
+                __expand_into(word, lookup, assigned)
+            } else {
+                Ok(current.unwrap_or_default())
+            }
+        }
+        ":=" => {
+            if unset_or_empty {
+                let value = __expand_into(word, lookup, assigned)?;
+                assigned.insert(name.to_string(), value.clone());
+                Ok(value)
+            } else {
+                Ok(current.unwrap_or_default())
+            }
+        }
+        ":+" => {
+            if unset_or_empty {
+                Ok(BString::new(vec![]))
+            } else {
+                __expand_into(word, lookup, assigned)
+            }
+        }
+        ":?" => {
+            if unset_or_empty {
+                let message = __expand_into(word, lookup, assigned)?;
+                Err(ExpandError::RequiredVarUnset {
+                    name: name.to_string(),
+                    message: message.to_str_lossy().into_owned(),
+                })
+            } else {
+                Ok(current.unwrap_or_default())
+            }
+        }
+        _ => unreachable!("ENVVAR_REGEX only captures the operators handled above"),
+    }
+}
+
+/// Expands every `$VAR`/`${VAR}` component found in `s`, resolving each variable name through
+/// `lookup` rather than the process environment. Returns the expanded bytes, unexpanded plain
+/// text included.
 ///
-/// - An envvar cannot be expanded
-/// - You don't have a home directory
-pub fn expand<S: AsRef<[u8]>>(s: S) -> Result<PathBuf, ExpandError> {
+/// Operates entirely on bytes (via `regex::bytes`), so only the envvar names themselves need to
+/// be valid UTF8 (guaranteed by their grammar); surrounding text and operator words may contain
+/// arbitrary, non-UTF8 bytes and are passed through unchanged.
+fn __expand_into<F>(
+    s: &[u8],
+    lookup: &mut F,
+    assigned: &mut HashMap<String, BString>,
+) -> Result<BString, ExpandError>
+where
+    F: FnMut(&str) -> Option<BString>,
+{
     static ENVVAR_REGEX: LazyLock<Regex> = LazyLock::new(|| {
         /*
          * Capture groups:
          * "envvar": The environment variable name
-         * "fallback": The fallback value, in its entirety
+         * "op": Which parameter-expansion operator was used, if any
+         * "word": The operator's operand, in its entirety
          */
-        Regex::new(r"(?<envvar>[a-zA-Z_]\w*)(?::-(?<fallback>.*?\}))?")
+        Regex::new(r"(?-u)^(?<envvar>[a-zA-Z_]\w*)(?:(?<op>:-|:=|:\+|:\?)(?<word>(?s:.*)))?$")
             .expect("invalid envvar regex")
     });
 
-    let bs = bstr::B(s.as_ref());
-    let comp_strs = __parse_string_components(bs);
+    let components = __parse_string_components(s);
+    let mut result = BString::new(vec![]);
 
-    for comp in comp_strs {
-        if !comp[0] == b'$' {
+    for comp in components {
+        if comp.first() != Some(&b'$') {
+            result.push_str(comp);
             continue;
         }
 
-        let trimmed = if comp[1] == b'{' {
-            // remove surrounding ${...}
-            &comp[2..comp.len() - 1]
-        } else {
-            // remove $...
-            &comp[1..]
+        let (body, trailing) = __split_envvar_component(comp);
+        let Some(caps) = ENVVAR_REGEX.captures(body) else {
+            // `$` didn't introduce a well-formed envvar reference (e.g. a bare `$`, `$5`, or an
+            // unterminated `${...}`); every shell passes text like that through unchanged rather
+            // than treating it as an error.
+            result.push_str(comp);
+            continue;
+        };
+        // guaranteed ASCII by ENVVAR_REGEX's `envvar` and `op` patterns
+        let name = std::str::from_utf8(&caps["envvar"]).expect("envvar is always ASCII");
+        let op = caps.name("op").map(|m| {
+            std::str::from_utf8(m.as_bytes()).expect("operator is always ASCII")
+        });
+
+        let current = assigned.get(name).cloned().or_else(|| lookup(name));
+
+        let value = match op {
+            Some(op) => {
+                let word = caps.name("word").map_or(b"".as_slice(), |m| m.as_bytes());
+                __eval_operator(name, op, word, current, lookup, assigned)?
+            }
+            None => current.ok_or_else(|| ExpandError::EnvvarReadError(name.to_string()))?,
         };
+
+        result.push_str(value.as_slice());
+        result.push_str(trailing);
     }
 
-    // TODO: maybe fancy-regex crate for lookbehind?
-    todo!()
+    Ok(result)
+}
+
+/// Returns the bytes that make up the current user's home directory, as reported by
+/// [`BASE_DIRS`].
+fn __home_dir_bytes() -> BString {
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        BString::from(BASE_DIRS.home_dir().as_os_str().as_bytes())
+    }
+    #[cfg(not(unix))]
+    {
+        BString::from(BASE_DIRS.home_dir().to_string_lossy().as_bytes())
+    }
+}
+
+/// Looks up `name` in the passwd database and returns the bytes of their home directory.
+///
+/// # Errors
+///
+/// Returns [`ExpandError::UnknownUser`] if no such user exists, or if this platform has no
+/// passwd database to consult.
+#[cfg(unix)]
+fn __user_home_dir_bytes(name: &str) -> Result<BString, ExpandError> {
+    use std::os::unix::ffi::OsStrExt;
+
+    let user = nix::unistd::User::from_name(name)
+        .ok()
+        .flatten()
+        .ok_or_else(|| ExpandError::UnknownUser(name.to_string()))?;
+
+    Ok(BString::from(user.dir.as_os_str().as_bytes()))
+}
+
+#[cfg(not(unix))]
+#[allow(clippy::unnecessary_wraps)]
+fn __user_home_dir_bytes(name: &str) -> Result<BString, ExpandError> {
+    Err(ExpandError::UnknownUser(name.to_string()))
+}
+
+/// Expands a leading `~` or `~user` in `bytes` into the relevant home directory, leaving
+/// everything else untouched.
+fn __expand_leading_tilde(bytes: BString) -> Result<BString, ExpandError> {
+    if bytes.first() != Some(&b'~') {
+        return Ok(bytes);
+    }
+
+    let rest = &bytes[1..];
+    let name_len = rest.iter().position(|&b| b == b'/').unwrap_or(rest.len());
+    let (name, remainder) = rest.split_at(name_len);
+
+    let mut home = if name.is_empty() {
+        __home_dir_bytes()
+    } else {
+        let name = name
+            .to_str()
+            .map_err(|_| ExpandError::UnknownUser(name.to_str_lossy().into_owned()))?;
+        __user_home_dir_bytes(name)?
+    };
+    home.extend_from_slice(remainder);
+
+    Ok(home)
+}
+
+/// Converts fully-expanded bytes into a [`PathBuf`], substituting a leading `~`/`~user` for the
+/// relevant user's home directory along the way.
+fn __bytes_to_pathbuf(bytes: BString) -> Result<PathBuf, ExpandError> {
+    let bytes = __expand_leading_tilde(bytes)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::ffi::OsStrExt;
+        Ok(PathBuf::from(std::ffi::OsStr::from_bytes(&bytes)))
+    }
+    #[cfg(not(unix))]
+    {
+        Ok(PathBuf::from(bytes.to_string_lossy().into_owned()))
+    }
+}
+
+/// Like [`expand`], but resolves every environment variable through `lookup` instead of the
+/// process environment.
+///
+/// `lookup` is invoked with each variable name found in `s` and should return `Some(value)` when
+/// the variable is defined. Returning `None` marks the variable as unset: its operator
+/// (`:-`, `:=`, `:+` or `:?`) is applied if one was given, or an
+/// [`ExpandError::EnvvarReadError`] is returned otherwise. A name already assigned by a `:=`
+/// earlier in `s` resolves to that value without calling `lookup`. This makes it possible to expand
+/// against an in-memory map, a config struct, or a test fixture without mutating the process
+/// environment.
+///
+/// # Arguments
+///
+/// - `s`: String to expand and convert
+/// - `lookup`: Called with each envvar name found in `s`
+///
+/// # Errors
+///
+/// An error is returned if:
+///
+/// - An envvar cannot be expanded
+/// - A `~user` reference names a user that doesn't exist
+/// - A `${VAR:?message}` variable is unset or empty
+///
+/// # Panics
+///
+/// Panics if the expanded string starts with a bare `~` and your home directory cannot be found.
+///
+/// # Examples
+///
+/// ```rust
+/// # use expandenv::expand_with;
+/// # fn main() -> Result<(), expandenv::errors::ExpandError> {
+/// let path = expand_with("$FOO/bar", |name| {
+///     (name == "FOO").then(|| "baz".into())
+/// })?;
+/// assert_eq!(std::path::PathBuf::from("baz/bar"), path);
+/// # Ok(())
+/// # }
+/// ```
+pub fn expand_with<S, F>(s: S, mut lookup: F) -> Result<PathBuf, ExpandError>
+where
+    S: AsRef<[u8]>,
+    F: FnMut(&str) -> Option<BString>,
+{
+    let expanded = __expand_into(s.as_ref(), &mut lookup, &mut HashMap::new())?;
+    __bytes_to_pathbuf(expanded)
+}
+
+/// Like [`expand`], but returns the raw expanded bytes instead of a [`PathBuf`], borrowing `s`
+/// unchanged when it contains nothing to expand.
+///
+/// Operates entirely on bytes via `regex::bytes`, so non-UTF8 byte sequences in `s` (valid on
+/// most Unix filesystems) pass through untouched instead of being rejected or lossily converted.
+/// Only resolves against the process environment and does not expand a leading `~`/`~user`; use
+/// [`expand`] or [`expand_with`] for that.
+///
+/// # Errors
+///
+/// An error is returned if:
+///
+/// - An envvar cannot be expanded
+/// - A `${VAR:?message}` variable is unset or empty
+///
+/// # Examples
+///
+/// ```rust
+/// # use std::borrow::Cow;
+/// # use expandenv::expand_bytes;
+/// # fn main() -> Result<(), expandenv::errors::ExpandError> {
+/// // no envvars to expand, so the input is borrowed unchanged
+/// let path = "some/plain/path";
+/// assert!(matches!(expand_bytes(path)?, Cow::Borrowed(_)));
+/// # Ok(())
+/// # }
+/// ```
+pub fn expand_bytes<S: AsRef<[u8]> + ?Sized>(s: &S) -> Result<Cow<'_, [u8]>, ExpandError> {
+    let bytes = s.as_ref();
+
+    if !bytes.contains(&b'$') {
+        return Ok(Cow::Borrowed(bytes));
+    }
+
+    let mut lookup = |name: &str| std::env::var(name).ok().map(BString::from);
+    let expanded = __expand_into(bytes, &mut lookup, &mut HashMap::new())?;
+
+    if expanded.as_slice() == bytes {
+        return Ok(Cow::Borrowed(bytes));
+    }
+
+    Ok(Cow::Owned(Vec::from(expanded)))
+}
+
+/// Convert a `&str` slice into a `PathBuf`, expanding envvars and the leading tilde `~`, if it
+/// is there.
+///
+/// The tilde (`~`) expands into the users home directory as defined by [`directories_next::BaseDirs::home_dir`].
+/// `~user` expands into `user`'s home directory instead, looked up from the system's passwd
+/// database on Unix; platforms without one return [`errors::ExpandError::UnknownUser`].
+///
+/// Environment variables expand into their value. Envvars may contain letters, numbers, and
+/// underscores (`_`), but they _must_ start with either a letter or an underscore after the
+/// dollar sign (`$`). Although more complicated syntax is technically allowed by most programming
+/// languages, I will not be supporting anything other than this basic structure because this is
+/// what most shells support and if you're doing something different, ask yourself why.
+///
+/// The POSIX parameter-expansion operators are supported for the rest: `${VAR:-word}` expands to
+/// `word` if `VAR` is unset or empty, `${VAR:=word}` does the same but also assigns `word` to
+/// `VAR` for any later reference, `${VAR:+word}` expands to `word` only if `VAR` is set and
+/// non-empty, and `${VAR:?message}` returns [`errors::ExpandError::RequiredVarUnset`] if `VAR` is
+/// unset or empty. `word` and `message` may themselves contain further envvars and are expanded
+/// the same way.
+///
+/// Resolves each variable against the process environment; use [`expand_with`] to resolve against
+/// a custom source instead.
+///
+/// # Arguments
+///
+/// - `s`: String to expand and convert
+///
+/// # Errors
+///
+/// An error is returned if:
+///
+/// - An envvar cannot be expanded
+/// - A `~user` reference names a user that doesn't exist
+/// - A `${VAR:?message}` variable is unset or empty
+///
+/// # Panics
+///
+/// Panics if the expanded string starts with a bare `~` and your home directory cannot be found.
+pub fn expand<S: AsRef<[u8]>>(s: S) -> Result<PathBuf, ExpandError> {
+    let expanded = expand_bytes(s.as_ref())?;
+    __bytes_to_pathbuf(BString::from(expanded.into_owned()))
 }
 
 #[cfg(test)]
@@ -215,20 +530,60 @@ mod tests {
 
     #[test]
     fn test_parses_string_with_braces() {
-        let expected = vec!["this is a ", "${within braces}", " string"];
-        let expected_str = expected.join("");
-        let actual = __parse_string_components(expected_str);
+        let expected: Vec<&[u8]> = vec![b"this is a ", b"${within braces}", b" string"];
+        let expected_str = expected.concat();
+        let actual = __parse_string_components(&expected_str);
 
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_parses_string_with_braces_but_no_dollar_sign() {
-        let expected = vec!["this is a {within braces} string"];
-        let expected_str = expected.join("");
-        let actual = __parse_string_components(expected_str);
+        let expected: Vec<&[u8]> = vec![b"this is a {within braces} string"];
+        let expected_str = expected.concat();
+        let actual = __parse_string_components(&expected_str);
+
+        assert_eq!(expected, actual);
+    }
+
+    #[test]
+    fn test_expand_bare_trailing_dollar_sign_is_literal() -> anyhow::Result<()> {
+        let expected = PathBuf::from("literal $");
+        let actual = expand("literal $")?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_dollar_sign_followed_by_digit_is_literal() -> anyhow::Result<()> {
+        let expected = PathBuf::from("I have $5");
+        let actual = expand("I have $5")?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_dollar_sign_followed_by_non_envvar_char_is_literal() -> anyhow::Result<()> {
+        let expected = PathBuf::from("a $ b");
+        let actual = expand("a $ b")?;
 
         assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_unterminated_braced_envvar_is_literal() -> anyhow::Result<()> {
+        let expected = PathBuf::from("${SV");
+        let actual = expand("${SV")?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
     }
 
     #[test]
@@ -315,4 +670,198 @@ mod tests {
 
         Ok(())
     }
+
+    #[test]
+    fn test_expand_with_custom_lookup() -> anyhow::Result<()> {
+        let mut lookups = std::collections::HashMap::new();
+        lookups.insert("FOO", "bar");
+
+        let expected = PathBuf::from("bar/baz");
+        let actual = expand_with("$FOO/baz", |name| lookups.get(name).map(|v| (*v).into()))?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_with_custom_lookup_does_not_touch_process_env() -> anyhow::Result<()> {
+        const TEST_ENVVAR: &str = "__EXPANDENV_CUSTOM_LOOKUP_ONLY";
+        assert!(std::env::var(TEST_ENVVAR).is_err());
+
+        let actual = expand_with(format!("${{{TEST_ENVVAR}:-fallback}}"), |_| None)?;
+
+        assert_eq!(PathBuf::from("fallback"), actual);
+        assert!(std::env::var(TEST_ENVVAR).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_with_nested_fallback_through_closure() -> anyhow::Result<()> {
+        let mut lookups = std::collections::HashMap::new();
+        lookups.insert("FOO", "bar");
+
+        let actual = expand_with("${MISSING:-${ALSO_MISSING:-$FOO}}", |name| {
+            lookups.get(name).map(|v| (*v).into())
+        })?;
+
+        assert_eq!(PathBuf::from("bar"), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_bare_tilde() -> anyhow::Result<()> {
+        let expected = BASE_DIRS.home_dir().join("file.txt");
+        let actual = expand("~/file.txt")?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn test_expand_tilde_user() -> anyhow::Result<()> {
+        let name = nix::unistd::User::from_uid(nix::unistd::getuid())
+            .context("failed to look up current user")?
+            .context("current user not found in passwd database")?
+            .name;
+
+        let expected = BASE_DIRS.home_dir().join("file.txt");
+        let actual = expand(format!("~{name}/file.txt"))?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_tilde_unknown_user_fails() {
+        match expand("~this_user_almost_certainly_does_not_exist/file.txt") {
+            Err(ExpandError::UnknownUser(_)) => {}
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_expand_use_alternate_value_when_set_and_non_empty() -> anyhow::Result<()> {
+        set_test_envvar().context("failed to set test envvar")?;
+        let actual = expand(format!("${{{TEST_ENVVAR_KEY}:+alternate}}"))?;
+
+        assert_eq!(PathBuf::from("alternate"), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_use_alternate_value_is_empty_when_unset() -> anyhow::Result<()> {
+        let actual = expand("${NO_WAY_YOU_HAVE_DEFINED_THIS:+alternate}")?;
+
+        assert_eq!(PathBuf::new(), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_use_alternate_value_is_empty_when_set_but_empty() -> anyhow::Result<()> {
+        let actual = expand_with("${EMPTY:+alternate}", |name| {
+            (name == "EMPTY").then(|| BString::new(vec![]))
+        })?;
+
+        assert_eq!(PathBuf::new(), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_required_var_unset_errors() {
+        match expand("${NO_WAY_YOU_HAVE_DEFINED_THIS:?must be set}") {
+            Err(ExpandError::RequiredVarUnset { name, message }) => {
+                assert_eq!(name, "NO_WAY_YOU_HAVE_DEFINED_THIS");
+                assert_eq!(message, "must be set");
+            }
+            res => panic!("expected error, got {res:?}"),
+        }
+    }
+
+    #[test]
+    fn test_expand_required_var_set_does_not_error() -> anyhow::Result<()> {
+        set_test_envvar().context("failed to set test envvar")?;
+        let expected = TEST_ENVVAR_VALUE.to_string();
+        let actual = expand(format!("${{{TEST_ENVVAR_KEY}:?must be set}}"))?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_assign_default_sets_value_for_later_references() -> anyhow::Result<()> {
+        let actual = expand_with(
+            "${MISSING:=assigned}/${MISSING}",
+            |name| (name == "OTHER").then(|| "unused".into()),
+        )?;
+
+        assert_eq!(PathBuf::from("assigned/assigned"), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_assign_default_does_not_overwrite_set_value() -> anyhow::Result<()> {
+        set_test_envvar().context("failed to set test envvar")?;
+        let expected = TEST_ENVVAR_VALUE.to_string();
+        let actual = expand(format!("${{{TEST_ENVVAR_KEY}:=assigned}}"))?;
+
+        assert_eq!(expected, actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_operator_word_is_recursively_expanded() -> anyhow::Result<()> {
+        set_test_envvar().context("failed to set test envvar")?;
+        let actual = expand(format!(
+            "${{{TEST_ENVVAR_KEY}:+${{ALSO_MISSING:-fallback}}}}"
+        ))?;
+
+        assert_eq!(PathBuf::from("fallback"), actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_bytes_borrows_input_without_envvars() -> anyhow::Result<()> {
+        let input = b"some/plain/path".as_slice();
+        let actual = expand_bytes(input)?;
+
+        assert!(matches!(actual, Cow::Borrowed(_)));
+        assert_eq!(input, &*actual);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_bytes_preserves_non_utf8_bytes() -> anyhow::Result<()> {
+        set_test_envvar().context("failed to set test envvar")?;
+
+        let mut input = b"/tmp/".to_vec();
+        input.extend_from_slice(b"$__EXPANDENV_TEST_VAR");
+        input.push(b'/');
+        input.push(0xFF); // not valid UTF8 on its own
+
+        let mut expected = b"/tmp/".to_vec();
+        expected.extend_from_slice(TEST_ENVVAR_VALUE.as_bytes());
+        expected.push(b'/');
+        expected.push(0xFF);
+
+        let actual = expand_bytes(input.as_slice())?;
+
+        assert_eq!(expected, &*actual);
+
+        Ok(())
+    }
 }
+